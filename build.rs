@@ -0,0 +1,30 @@
+use std::process::Command;
+
+fn main() {
+    let git_hash = command_output("git", &["rev-parse", "--short", "HEAD"]);
+    println!("cargo:rustc-env=GIT_HASH={}", git_hash);
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]);
+    println!("cargo:rustc-env=RUSTC_VERSION={}", rustc_version);
+
+    // Refresh the hash when a new commit is checked out
+    if std::path::Path::new(".git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        println!("cargo:rerun-if-changed=.git/refs");
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
+
+/// Returns trimmed stdout of the command, or "unknown" if it cannot be run
+fn command_output(cmd: &str, args: &[&str]) -> String {
+    Command::new(cmd)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|stdout| stdout.trim().to_string())
+        .filter(|stdout| !stdout.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
@@ -0,0 +1,933 @@
+//! Minimal Solana JSON-RPC client covering only the methods the monitor needs
+
+use anyhow::Context;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Program owning the validator info accounts published with `solana validator-info`
+const CONFIG_PROGRAM_ID: &str = "Config1111111111111111111111111111111111111";
+
+#[derive(Clone)]
+pub struct RpcClient {
+    http_client: reqwest::Client,
+    /// Shared by the clones, so that all of them fail over together
+    endpoints: Arc<Endpoints>,
+    /// Passed to the methods which take one, `None` for the default of the
+    /// node
+    commitment: Option<String>,
+    metrics: Option<RequestMetrics>,
+}
+
+// The endpoint may hold an API key
+impl std::fmt::Debug for RpcClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let urls: Vec<_> = self
+            .endpoints
+            .urls
+            .iter()
+            .map(|url| crate::redact_url(url))
+            .collect();
+        f.debug_struct("RpcClient")
+            .field("urls", &urls)
+            .field("commitment", &self.commitment)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Counts every request by method and outcome and times it by method,
+/// exports the state of the endpoints by their redacted URL
+#[derive(Clone)]
+pub struct RequestMetrics {
+    pub requests_total: prometheus::IntCounterVec,
+    pub duration_seconds: prometheus::HistogramVec,
+    pub endpoint_healthy: prometheus::IntGaugeVec,
+    pub endpoint_active: prometheus::IntGaugeVec,
+}
+
+/// Endpoints in order of preference and which of them requests go to
+struct Endpoints {
+    urls: Vec<String>,
+    failover: Mutex<Failover>,
+    /// Whether the endpoint metrics are about these endpoints
+    exported: bool,
+}
+
+/// Requests go to the active endpoint. When it fails the next healthy one
+/// takes over, or just the next one when none is healthy. A successful
+/// health check of an endpoint preferred over the active one returns the
+/// traffic to it.
+#[derive(Debug, Clone, PartialEq)]
+struct Failover {
+    active: usize,
+    healthy: Vec<bool>,
+}
+
+impl Failover {
+    fn new(endpoints: usize) -> Self {
+        Self {
+            active: 0,
+            healthy: vec![true; endpoints],
+        }
+    }
+
+    fn record_success(&mut self, endpoint: usize) {
+        self.healthy[endpoint] = true;
+    }
+
+    fn record_failure(&mut self, endpoint: usize) {
+        self.healthy[endpoint] = false;
+        if endpoint != self.active {
+            return;
+        }
+        let len = self.healthy.len();
+        let mut following = (1..len).map(|step| (endpoint + step) % len);
+        self.active = following
+            .clone()
+            .find(|&next| self.healthy[next])
+            .or_else(|| following.next())
+            .unwrap_or(endpoint);
+    }
+
+    fn record_health_check(&mut self, endpoint: usize, healthy: bool) {
+        self.healthy[endpoint] = healthy;
+        if healthy && endpoint < self.active {
+            self.active = endpoint;
+        }
+    }
+}
+
+impl Endpoints {
+    fn new(urls: Vec<String>, exported: bool) -> Self {
+        let failover = Mutex::new(Failover::new(urls.len()));
+        Self {
+            urls,
+            failover,
+            exported,
+        }
+    }
+
+    fn failover(&self) -> std::sync::MutexGuard<'_, Failover> {
+        self.failover
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn export(&self, failover: &Failover, metrics: &RequestMetrics) {
+        if !self.exported {
+            return;
+        }
+        for (endpoint, url) in self.urls.iter().enumerate() {
+            let url = crate::redact_url(url);
+            metrics
+                .endpoint_healthy
+                .with_label_values(&[&url])
+                .set(failover.healthy[endpoint].into());
+            metrics
+                .endpoint_active
+                .with_label_values(&[&url])
+                .set((failover.active == endpoint).into());
+        }
+    }
+}
+
+/// Whether another endpoint may answer the request, when the endpoint
+/// cannot be reached, did not respond in time or failed itself
+fn is_endpoint_failure(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>().is_some_and(|err| {
+        err.is_connect()
+            || err.is_timeout()
+            || err.status().is_some_and(|status| status.is_server_error())
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochInfo {
+    pub epoch: u64,
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+    pub absolute_slot: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct VoteAccounts {
+    pub current: Vec<RpcVoteAccount>,
+    pub delinquent: Vec<RpcVoteAccount>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteAccount {
+    pub vote_pubkey: String,
+    pub node_pubkey: String,
+    /// Activated stake in lamports
+    pub activated_stake: u64,
+    /// Credits in total and before each of the last epochs
+    #[serde(default)]
+    pub epoch_credits: Vec<(u64, u64, u64)>,
+    /// Slot of the most recent vote
+    #[serde(default)]
+    pub last_vote: u64,
+}
+
+impl RpcVoteAccount {
+    /// Credits earned in the epoch, 0 when it did not earn any
+    pub fn credits_in_epoch(&self, epoch: u64) -> u64 {
+        self.epoch_credits
+            .iter()
+            .find(|(credits_epoch, _, _)| *credits_epoch == epoch)
+            .map_or(0, |(_, credits, previous_credits)| {
+                credits.saturating_sub(*previous_credits)
+            })
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ProgramAccount {
+    account: ParsedAccount,
+}
+
+#[derive(serde::Deserialize)]
+struct ParsedAccount {
+    /// Parsed object for known account types, encoded bytes otherwise
+    data: serde_json::Value,
+}
+
+/// Account as returned by `getAccountInfo` and `getProgramAccounts`
+#[cfg(feature = "rpc-backend")]
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Account {
+    pub lamports: u64,
+    pub owner: String,
+    /// Parsed object with `jsonParsed` encoding for known account types,
+    /// `[data, encoding]` otherwise
+    pub data: serde_json::Value,
+}
+
+#[cfg(feature = "rpc-backend")]
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct KeyedAccount {
+    pub pubkey: String,
+    pub account: Account,
+}
+
+/// Result of the methods which report the slot they were evaluated at
+#[cfg(feature = "rpc-backend")]
+#[derive(serde::Deserialize)]
+struct WithContext<T> {
+    value: T,
+}
+
+#[derive(serde::Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcClient {
+    pub fn new(url: String) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            endpoints: Arc::new(Endpoints::new(vec![url], false)),
+            commitment: None,
+            metrics: None,
+        }
+    }
+
+    /// Client of the endpoints of `[rpc]` with its other settings, `None` if
+    /// no URL is configured
+    pub fn from_config(
+        config: &crate::RpcConfig,
+        metrics: RequestMetrics,
+    ) -> anyhow::Result<Option<Self>> {
+        let urls = config.endpoints();
+        if urls.is_empty() {
+            return Ok(None);
+        }
+        let http_client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .default_headers(header_map(&config.headers)?)
+            .build()
+            .context("Failed to create the RPC client")?;
+        let endpoints = Endpoints::new(urls.into_iter().cloned().collect(), true);
+        endpoints.export(&endpoints.failover(), &metrics);
+        Ok(Some(Self {
+            http_client,
+            endpoints: Arc::new(endpoints),
+            commitment: Some(config.commitment.clone()),
+            metrics: Some(metrics),
+        }))
+    }
+
+    /// Client of another endpoint sharing the connection pool and settings,
+    /// without failover
+    #[cfg(feature = "rpc-backend")]
+    pub fn with_url(&self, url: String) -> Self {
+        Self {
+            // The endpoint metrics are those of [rpc]
+            endpoints: Arc::new(Endpoints::new(vec![url], false)),
+            ..self.clone()
+        }
+    }
+
+    /// Endpoint requests currently go to
+    #[cfg(feature = "rpc-backend")]
+    pub fn url(&self) -> &str {
+        &self.endpoints.urls[self.endpoints.failover().active]
+    }
+
+    /// Whether requests can fail over to another endpoint
+    pub fn has_failover(&self) -> bool {
+        self.endpoints.urls.len() > 1
+    }
+
+    /// Sends `getHealth` to every endpoint but the active one, whose health
+    /// shows in the requests, and returns to the most preferred healthy one
+    pub async fn check_endpoints(&self) {
+        let active = self.endpoints.failover().active;
+        for (endpoint, url) in self.endpoints.urls.iter().enumerate() {
+            if endpoint == active {
+                continue;
+            }
+            let res: anyhow::Result<String> =
+                self.send(url, "getHealth", serde_json::json!([])).await;
+            if let Err(err) = &res {
+                tracing::debug!(
+                    "Health check of RPC endpoint {} failed: {:#}",
+                    crate::redact_url(url),
+                    err
+                );
+            }
+            self.update_failover(|failover| failover.record_health_check(endpoint, res.is_ok()));
+        }
+    }
+
+    fn update_failover(&self, update: impl FnOnce(&mut Failover)) {
+        let mut failover = self.endpoints.failover();
+        let active = failover.active;
+        update(&mut failover);
+        if failover.active != active {
+            tracing::warn!(
+                "Switched RPC requests from {} to {}",
+                crate::redact_url(&self.endpoints.urls[active]),
+                crate::redact_url(&self.endpoints.urls[failover.active])
+            );
+        }
+        if let Some(metrics) = &self.metrics {
+            self.endpoints.export(&failover, metrics);
+        }
+    }
+
+    /// Configuration object of a request with the commitment added
+    fn with_commitment(&self, mut config: serde_json::Value) -> serde_json::Value {
+        if let Some(commitment) = &self.commitment {
+            config["commitment"] = commitment.as_str().into();
+        }
+        config
+    }
+
+    /// The account, `None` if it does not exist
+    #[cfg(feature = "rpc-backend")]
+    pub async fn get_account_info(
+        &self,
+        pubkey: &str,
+        encoding: &str,
+    ) -> anyhow::Result<Option<Account>> {
+        let account: WithContext<Option<Account>> = self
+            .call(
+                "getAccountInfo",
+                serde_json::json!([
+                    pubkey,
+                    self.with_commitment(serde_json::json!({"encoding": encoding}))
+                ]),
+            )
+            .await?;
+        Ok(account.value)
+    }
+
+    /// Accounts of the program matching all `filters`, like
+    /// `{"memcmp": {"offset": 0, "bytes": ".."}}`
+    #[cfg(feature = "rpc-backend")]
+    pub async fn get_program_accounts(
+        &self,
+        program_id: &str,
+        encoding: &str,
+        filters: Vec<serde_json::Value>,
+    ) -> anyhow::Result<Vec<KeyedAccount>> {
+        self.call(
+            "getProgramAccounts",
+            serde_json::json!([
+                program_id,
+                self.with_commitment(serde_json::json!({"encoding": encoding, "filters": filters}))
+            ]),
+        )
+        .await
+    }
+
+    pub async fn get_epoch_info(&self) -> anyhow::Result<EpochInfo> {
+        self.call(
+            "getEpochInfo",
+            serde_json::json!([self.with_commitment(serde_json::json!({}))]),
+        )
+        .await
+    }
+
+    pub async fn get_vote_accounts(&self) -> anyhow::Result<VoteAccounts> {
+        self.call(
+            "getVoteAccounts",
+            serde_json::json!([self.with_commitment(serde_json::json!({}))]),
+        )
+        .await
+    }
+
+    /// Published validator names by identity pubkey
+    pub async fn get_validator_names(&self) -> anyhow::Result<HashMap<String, String>> {
+        let accounts: Vec<ProgramAccount> = self
+            .call(
+                "getProgramAccounts",
+                serde_json::json!([
+                    CONFIG_PROGRAM_ID,
+                    self.with_commitment(serde_json::json!({"encoding": "jsonParsed"}))
+                ]),
+            )
+            .await?;
+        Ok(accounts
+            .iter()
+            .filter_map(|program_account| {
+                let parsed = program_account.account.data.get("parsed")?;
+                if parsed.get("type")?.as_str()? != "validatorInfo" {
+                    return None;
+                }
+                // The identity is the signing key, the other one marks the
+                // account as validator info
+                let identity = parsed
+                    .pointer("/info/keys")?
+                    .as_array()?
+                    .iter()
+                    .find(|key| {
+                        key.get("signer").and_then(|signer| signer.as_bool()) == Some(true)
+                    })?
+                    .get("pubkey")?
+                    .as_str()?;
+                let name = parsed.pointer("/info/configData/name")?.as_str()?;
+                Some((identity.to_string(), name.to_string()))
+            })
+            .collect())
+    }
+
+    async fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> anyhow::Result<T> {
+        let started = std::time::Instant::now();
+        let res = self.send_with_failover(method, params).await;
+        if let Some(metrics) = &self.metrics {
+            let outcome = if res.is_ok() { "success" } else { "error" };
+            metrics
+                .requests_total
+                .with_label_values(&[method, outcome])
+                .inc();
+            metrics
+                .duration_seconds
+                .with_label_values(&[method])
+                .observe(started.elapsed().as_secs_f64());
+        }
+        res
+    }
+
+    /// Tries each endpoint once, starting with the active one, as long as
+    /// they fail themselves
+    async fn send_with_failover<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> anyhow::Result<T> {
+        let mut attempts = self.endpoints.urls.len();
+        loop {
+            let endpoint = self.endpoints.failover().active;
+            let url = &self.endpoints.urls[endpoint];
+            match self.send(url, method, params.clone()).await {
+                Ok(result) => {
+                    self.update_failover(|failover| failover.record_success(endpoint));
+                    return Ok(result);
+                }
+                Err(err) if is_endpoint_failure(&err) => {
+                    self.update_failover(|failover| failover.record_failure(endpoint));
+                    attempts -= 1;
+                    if attempts == 0 {
+                        return Err(err);
+                    }
+                    tracing::warn!(
+                        "RPC endpoint {} failed, trying the next one: {:#}",
+                        crate::redact_url(url),
+                        err
+                    );
+                }
+                // The request itself is the problem
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> anyhow::Result<T> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let response: RpcResponse<T> = self
+            .http_client
+            .post(url)
+            .json(&request)
+            .send()
+            .await
+            .with_context(|| format!("Failed to send {} request", method))?
+            .error_for_status()
+            .with_context(|| format!("RPC returned error status for {}", method))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {} response", method))?;
+
+        if let Some(err) = response.error {
+            anyhow::bail!(
+                "RPC error for {}: {} (code {})",
+                method,
+                err.message,
+                err.code
+            );
+        }
+        response
+            .result
+            .with_context(|| format!("RPC response for {} has no result", method))
+    }
+}
+
+/// Headers sent with every request, rejecting invalid names and values
+pub fn header_map(
+    headers: &std::collections::BTreeMap<String, String>,
+) -> anyhow::Result<reqwest::header::HeaderMap> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let name = reqwest::header::HeaderName::try_from(name)
+                .with_context(|| format!("Invalid header name '{}'", name))?;
+            let mut value = reqwest::header::HeaderValue::try_from(value)
+                .with_context(|| format!("Invalid value of header '{}'", name))?;
+            value.set_sensitive(true);
+            Ok((name, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Serves canned JSON-RPC responses by method name and returns its URL
+    pub(crate) async fn mock_rpc(responses: Vec<(&'static str, serde_json::Value)>) -> String {
+        let responses: std::collections::HashMap<_, _> = responses.into_iter().collect();
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(move |axum::Json(request): axum::Json<serde_json::Value>| {
+                let response = responses.get(request["method"].as_str().unwrap()).cloned();
+                async move {
+                    axum::Json(match response {
+                        Some(result) => {
+                            serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": result})
+                        }
+                        None => serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": 1,
+                            "error": {"code": -32601, "message": "Method not found"}
+                        }),
+                    })
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn gets_epoch_info() {
+        let url = mock_rpc(vec![(
+            "getEpochInfo",
+            serde_json::json!({
+                "absoluteSlot": 166598,
+                "blockHeight": 166500,
+                "epoch": 27,
+                "slotIndex": 2790,
+                "slotsInEpoch": 8192,
+                "transactionCount": 22661093
+            }),
+        )])
+        .await;
+
+        let epoch_info = RpcClient::new(url).get_epoch_info().await.unwrap();
+
+        assert_eq!(
+            epoch_info,
+            EpochInfo {
+                epoch: 27,
+                slot_index: 2790,
+                slots_in_epoch: 8192,
+                absolute_slot: 166598,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn gets_vote_accounts() {
+        let url = mock_rpc(vec![(
+            "getVoteAccounts",
+            serde_json::json!({
+                "current": [{
+                    "activatedStake": 42000000000u64,
+                    "commission": 0,
+                    "epochCredits": [[1, 64, 0], [2, 192, 64]],
+                    "epochVoteAccount": true,
+                    "lastVote": 147,
+                    "nodePubkey": "B97CCUW3AEZFGy6uUg6zUdnNYvnVq5VG8PUtb2HayTDD",
+                    "rootSlot": 42,
+                    "votePubkey": "3ZT31jkAGhUaw8jsy4bTknwBMP8i4Eueh52By4zXcsVw"
+                }],
+                "delinquent": []
+            }),
+        )])
+        .await;
+
+        let vote_accounts = RpcClient::new(url).get_vote_accounts().await.unwrap();
+
+        assert_eq!(
+            vote_accounts,
+            VoteAccounts {
+                current: vec![RpcVoteAccount {
+                    vote_pubkey: "3ZT31jkAGhUaw8jsy4bTknwBMP8i4Eueh52By4zXcsVw".to_string(),
+                    node_pubkey: "B97CCUW3AEZFGy6uUg6zUdnNYvnVq5VG8PUtb2HayTDD".to_string(),
+                    activated_stake: 42_000_000_000,
+                    epoch_credits: vec![(1, 64, 0), (2, 192, 64)],
+                    last_vote: 147,
+                }],
+                delinquent: vec![],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn gets_validator_names() {
+        let url = mock_rpc(vec![("getProgramAccounts", validator_infos())]).await;
+
+        let names = RpcClient::new(url).get_validator_names().await.unwrap();
+
+        assert_eq!(
+            names,
+            HashMap::from([(
+                "G795LTaKPbC7eyRePBTey9VZAwEygJGqinnLRuXX2E4T".to_string(),
+                "Example Validator".to_string()
+            )])
+        );
+    }
+
+    /// `getProgramAccounts` result with one validator info and one account of
+    /// another type
+    pub(crate) fn validator_infos() -> serde_json::Value {
+        serde_json::json!([
+            {
+                "pubkey": "5wTag3bCWkxBXBdfrMV2tBydE7mmFwSPbrBEW3p5XXcF",
+                "account": {
+                    "data": {
+                        "parsed": {
+                            "type": "validatorInfo",
+                            "info": {
+                                "keys": [
+                                    {"pubkey": "Va1idator1nfo111111111111111111111111111111", "signer": false},
+                                    {"pubkey": "G795LTaKPbC7eyRePBTey9VZAwEygJGqinnLRuXX2E4T", "signer": true}
+                                ],
+                                "configData": {"name": "Example Validator", "website": "https://example.com"}
+                            }
+                        },
+                        "program": "config",
+                        "space": 650
+                    },
+                    "executable": false,
+                    "lamports": 10000000,
+                    "owner": "Config1111111111111111111111111111111111111"
+                }
+            },
+            {
+                "pubkey": "StakeConfig11111111111111111111111111111111",
+                "account": {
+                    "data": {
+                        "parsed": {"type": "stakeConfig", "info": {"slashPenalty": 12, "warmupCooldownRate": 0.25}},
+                        "program": "config",
+                        "space": 10
+                    },
+                    "executable": false,
+                    "lamports": 960480,
+                    "owner": "Config1111111111111111111111111111111111111"
+                }
+            }
+        ])
+    }
+
+    fn request_metrics() -> RequestMetrics {
+        RequestMetrics {
+            requests_total: prometheus::IntCounterVec::new(
+                prometheus::Opts::new("requests_total", "Requests"),
+                &["method", "outcome"],
+            )
+            .unwrap(),
+            duration_seconds: prometheus::HistogramVec::new(
+                prometheus::HistogramOpts::new("duration_seconds", "Durations"),
+                &["method"],
+            )
+            .unwrap(),
+            endpoint_healthy: prometheus::IntGaugeVec::new(
+                prometheus::Opts::new("endpoint_healthy", "Healthy"),
+                &["endpoint"],
+            )
+            .unwrap(),
+            endpoint_active: prometheus::IntGaugeVec::new(
+                prometheus::Opts::new("endpoint_active", "Active"),
+                &["endpoint"],
+            )
+            .unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_rpc_settings_and_counts_requests() {
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(
+                |headers: axum::http::HeaderMap,
+                 axum::Json(request): axum::Json<serde_json::Value>| async move {
+                    let authorized = headers.get("authorization").and_then(|value| value.to_str().ok())
+                        == Some("Bearer secret");
+                    let finalized = request["params"][0]["commitment"] == "finalized";
+                    axum::Json(if authorized && finalized {
+                        serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": {
+                            "absoluteSlot": 166598,
+                            "epoch": 27,
+                            "slotIndex": 2790,
+                            "slotsInEpoch": 8192
+                        }})
+                    } else {
+                        serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": 1,
+                            "error": {"code": -32600, "message": format!("Unexpected request {request}")}
+                        })
+                    })
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let metrics = request_metrics();
+        let config = crate::RpcConfig {
+            url: Some(url),
+            commitment: "finalized".to_string(),
+            headers: [("Authorization".to_string(), "Bearer secret".to_string())].into(),
+            ..Default::default()
+        };
+
+        let client = RpcClient::from_config(&config, metrics.clone())
+            .unwrap()
+            .unwrap();
+        client.get_epoch_info().await.unwrap();
+        client.get_vote_accounts().await.unwrap_err();
+
+        let count = |method, outcome| {
+            metrics
+                .requests_total
+                .with_label_values(&[method, outcome])
+                .get()
+        };
+        assert_eq!(count("getEpochInfo", "success"), 1);
+        assert_eq!(count("getVoteAccounts", "error"), 1);
+        assert_eq!(
+            metrics
+                .duration_seconds
+                .with_label_values(&["getEpochInfo"])
+                .get_sample_count(),
+            1
+        );
+        assert!(!format!("{client:?}").contains("secret"));
+    }
+
+    #[test]
+    fn rotates_endpoints() {
+        enum Event {
+            Success(usize),
+            Failure(usize),
+            HealthCheck(usize, bool),
+        }
+        use Event::*;
+
+        for (events, active, healthy) in [
+            (vec![Failure(0)], 1, vec![false, true, true]),
+            (vec![Failure(0), Failure(1)], 2, vec![false, false, true]),
+            // Without a healthy endpoint the next one is tried
+            (
+                vec![Failure(0), Failure(1), Failure(2)],
+                0,
+                vec![false, false, false],
+            ),
+            (vec![Failure(1), Failure(0)], 2, vec![false, false, true]),
+            (vec![Failure(2), Success(0)], 0, vec![true, true, false]),
+            (vec![Failure(0), Success(0)], 1, vec![true, true, true]),
+            (
+                vec![Failure(0), Failure(1), HealthCheck(0, true)],
+                0,
+                vec![true, false, true],
+            ),
+            (
+                vec![Failure(0), Failure(1), HealthCheck(1, true)],
+                1,
+                vec![false, true, true],
+            ),
+            (
+                vec![Failure(0), HealthCheck(2, true)],
+                1,
+                vec![false, true, true],
+            ),
+            (
+                vec![Failure(0), HealthCheck(0, false)],
+                1,
+                vec![false, true, true],
+            ),
+            (
+                vec![HealthCheck(1, false), Failure(0)],
+                2,
+                vec![false, false, true],
+            ),
+        ] {
+            let mut failover = Failover::new(3);
+            for event in events {
+                match event {
+                    Success(endpoint) => failover.record_success(endpoint),
+                    Failure(endpoint) => failover.record_failure(endpoint),
+                    HealthCheck(endpoint, healthy) => {
+                        failover.record_health_check(endpoint, healthy)
+                    }
+                }
+            }
+            assert_eq!(failover, Failover { active, healthy });
+        }
+
+        let mut failover = Failover::new(1);
+        failover.record_failure(0);
+        assert_eq!(failover.active, 0);
+    }
+
+    /// Endpoint responding with 503 while `failing` is set
+    async fn mock_endpoint(failing: Arc<std::sync::atomic::AtomicBool>) -> String {
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(move |axum::Json(request): axum::Json<serde_json::Value>| {
+                let failing = failing.load(std::sync::atomic::Ordering::SeqCst);
+                async move {
+                    if failing {
+                        return Err(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+                    }
+                    Ok(axum::Json(match request["method"].as_str() {
+                        Some("getHealth") => {
+                            serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": "ok"})
+                        }
+                        Some("getEpochInfo") => {
+                            serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": {
+                                "absoluteSlot": 166598,
+                                "epoch": 27,
+                                "slotIndex": 2790,
+                                "slotsInEpoch": 8192
+                            }})
+                        }
+                        _ => serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": 1,
+                            "error": {"code": -32601, "message": "Method not found"}
+                        }),
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_next_endpoint() {
+        let primary_failing = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let primary = mock_endpoint(primary_failing.clone()).await;
+        let unreachable = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let backup = mock_endpoint(Arc::default()).await;
+        let metrics = request_metrics();
+        let config = crate::RpcConfig {
+            urls: vec![primary.clone(), unreachable.clone(), backup.clone()],
+            ..Default::default()
+        };
+        let client = RpcClient::from_config(&config, metrics.clone())
+            .unwrap()
+            .unwrap();
+        let gauges = |gauge: &prometheus::IntGaugeVec| {
+            [&primary, &unreachable, &backup].map(|url| gauge.with_label_values(&[url]).get())
+        };
+
+        assert_eq!(gauges(&metrics.endpoint_active), [1, 0, 0]);
+        assert_eq!(client.get_epoch_info().await.unwrap().epoch, 27);
+        assert_eq!(gauges(&metrics.endpoint_active), [0, 0, 1]);
+        assert_eq!(gauges(&metrics.endpoint_healthy), [0, 0, 1]);
+
+        // Errors of the request itself stay with the endpoint
+        client.get_vote_accounts().await.unwrap_err();
+        assert_eq!(gauges(&metrics.endpoint_active), [0, 0, 1]);
+
+        client.check_endpoints().await;
+        assert_eq!(gauges(&metrics.endpoint_active), [0, 0, 1]);
+
+        primary_failing.store(false, std::sync::atomic::Ordering::SeqCst);
+        client.check_endpoints().await;
+        assert_eq!(gauges(&metrics.endpoint_active), [1, 0, 0]);
+        assert_eq!(gauges(&metrics.endpoint_healthy), [1, 0, 1]);
+        assert_eq!(client.get_epoch_info().await.unwrap().epoch, 27);
+        assert_eq!(
+            metrics
+                .requests_total
+                .with_label_values(&["getEpochInfo", "success"])
+                .get(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_rpc_errors() {
+        let url = mock_rpc(vec![]).await;
+
+        let err = RpcClient::new(url).get_epoch_info().await.unwrap_err();
+
+        assert!(format!("{err:#}").contains("Method not found"), "{err:#}");
+    }
+}
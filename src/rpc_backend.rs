@@ -0,0 +1,696 @@
+//! Fetch backend reading the accounts of the validator bonds program straight
+//! from a Solana RPC endpoint, so that no bonds CLI is needed
+
+use super::{
+    redact_url, select_bond, Account, Address, BondData, FetchError, FetchSource,
+    FundingStakeAccount, ShowBondOutput, VoteAccount, WithdrawRequest, LAMPORTS_PER_SOL,
+    SOL_DECIMALS,
+};
+use crate::rpc::{self, RpcClient};
+use anyhow::Context;
+
+/// Program of the Marinade validator bonds, unless `expected_program_id` is set
+const BONDS_PROGRAM_ID: &str = "vBoNdEvzMrSai7is21XgVYik65mqtaKXuSdMBJ1xkW4";
+const STAKE_PROGRAM_ID: &str = "Stake11111111111111111111111111111111111111";
+
+/// Anchor account discriminators, the first 8 bytes of
+/// `sha256("account:<Name>")`
+const BOND_DISCRIMINATOR: [u8; 8] = [224, 128, 48, 251, 182, 246, 111, 196];
+const WITHDRAW_REQUEST_DISCRIMINATOR: [u8; 8] = [186, 239, 174, 191, 189, 13, 47, 196];
+
+/// Bond accounts hold the config, vote account and authority pubkeys after
+/// the discriminator
+const BOND_VOTE_ACCOUNT_OFFSET: usize = 40;
+const BOND_LEN: usize = 104;
+/// Withdraw requests hold the vote account and bond pubkeys followed by the
+/// epoch, requested and withdrawn amounts
+const WITHDRAW_REQUEST_EPOCH_OFFSET: usize = 72;
+const WITHDRAW_REQUEST_LEN: usize = 96;
+/// Offsets of the withdrawer and the voter of delegated stake accounts
+const STAKE_WITHDRAWER_OFFSET: usize = 44;
+const STAKE_VOTER_OFFSET: usize = 124;
+
+#[derive(Debug)]
+pub struct RpcBackend {
+    program_id: String,
+}
+
+impl RpcBackend {
+    pub fn new(program_id: Option<String>) -> Self {
+        Self {
+            program_id: program_id.unwrap_or_else(|| BONDS_PROGRAM_ID.to_string()),
+        }
+    }
+
+    /// Bond of the address, which is a bond or a vote account, read from the
+    /// `cli_rpc_url` of the address or the endpoint of the client
+    pub async fn get_bond(
+        &self,
+        client: &RpcClient,
+        addr: &Address,
+        timeout: std::time::Duration,
+    ) -> Result<BondData, FetchError> {
+        read_with_timeout(client, addr, timeout, |client| async move {
+            self.read_bond(&client, addr).await
+        })
+        .await
+    }
+
+    /// Fails with a [FetchError] wrapped in the error when the address has
+    /// no unique bond
+    async fn read_bond(&self, client: &RpcClient, addr: &Address) -> anyhow::Result<BondData> {
+        let account = client
+            .get_account_info(&addr.address, "base64")
+            .await?
+            .ok_or_else(|| FetchError::BondNotFound {
+                detail: "the account does not exist".to_string(),
+            })?;
+        let candidates = if account.owner == self.program_id {
+            let data = account_data(&account)?;
+            if !data.starts_with(&BOND_DISCRIMINATOR) {
+                return Err(FetchError::BondNotFound {
+                    detail: format!("{} is not a bond account", addr.address),
+                }
+                .into());
+            }
+            vec![self.bond_data(addr.address.clone(), decode_bond(&data)?)]
+        } else {
+            // Anything else is taken for a vote account
+            let bonds = client
+                .get_program_accounts(
+                    &self.program_id,
+                    "base64",
+                    vec![
+                        memcmp(0, &BOND_DISCRIMINATOR),
+                        memcmp(BOND_VOTE_ACCOUNT_OFFSET, &decode_pubkey(&addr.address)?),
+                    ],
+                )
+                .await?;
+            if bonds.is_empty() {
+                return Err(FetchError::BondNotFound {
+                    detail: "no bond account has it as vote account".to_string(),
+                }
+                .into());
+            }
+            bonds
+                .iter()
+                .map(|bond| {
+                    let account = decode_bond(&account_data(&bond.account)?)?;
+                    Ok(self.bond_data(bond.pubkey.clone(), account))
+                })
+                .collect::<anyhow::Result<_>>()?
+        };
+        let mut bond_data = select_bond(ShowBondOutput::Multiple(candidates), addr)?;
+        self.fill_funding(client, &mut bond_data).await?;
+        Ok(bond_data)
+    }
+
+    /// Bond without its vote account and funding
+    fn bond_data(&self, public_key: String, account: Account) -> BondData {
+        BondData {
+            program_id: self.program_id.clone(),
+            public_key,
+            account,
+            vote_account: None,
+            bond_mint: None,
+            amount_owned: format_sol(0),
+            amount_active: format_sol(0),
+            number_active_stake_accounts: 0,
+            amount_at_settlements: format_sol(0),
+            number_settlement_stake_accounts: 0,
+            amount_to_withdraw: format_sol(0),
+            withdraw_request: WithdrawRequest::None,
+            lookup: None,
+            source: FetchSource::Rpc,
+            funding_stake_accounts: None,
+        }
+    }
+
+    /// Adds the vote account, the stake accounts funded to the bond and its
+    /// withdraw request
+    async fn fill_funding(
+        &self,
+        client: &RpcClient,
+        bond_data: &mut BondData,
+    ) -> anyhow::Result<()> {
+        let program_id = decode_pubkey(&self.program_id)?;
+        let bond = decode_pubkey(&bond_data.public_key)?;
+        let bonds_authority = bonds_authority(bond_data)?;
+        let withdraw_request_address = bs58::encode(find_program_address(
+            &[b"withdraw_account", &bond],
+            &program_id,
+        ))
+        .into_string();
+
+        let (vote_account, stake_accounts, withdraw_request) = tokio::try_join!(
+            client.get_account_info(&bond_data.account.vote_account, "jsonParsed"),
+            client.get_program_accounts(
+                STAKE_PROGRAM_ID,
+                "jsonParsed",
+                funding_stake_filters(&bonds_authority, bond_data)?,
+            ),
+            client.get_account_info(&withdraw_request_address, "base64"),
+        )?;
+
+        // Like the CLI, a vote account which does not load leaves it out
+        bond_data.vote_account = vote_account
+            .and_then(|account| account.data.pointer("/parsed/info").cloned())
+            .and_then(|info| serde_json::from_value::<VoteAccount>(info).ok());
+        bond_data.bond_mint = match &bond_data.vote_account {
+            Some(vote_account) => Some(
+                bs58::encode(find_program_address(
+                    &[
+                        b"bond_mint",
+                        &bond,
+                        &decode_pubkey(&vote_account.node_pubkey)?,
+                    ],
+                    &program_id,
+                ))
+                .into_string(),
+            ),
+            None => None,
+        };
+
+        // Stake accounts staying with the bond are active, the others were
+        // handed over to settlements
+        let mut owned = 0u64;
+        let mut at_settlements = 0u64;
+        for stake_account in &stake_accounts {
+            let staker = stake_account
+                .account
+                .data
+                .pointer("/parsed/info/meta/authorized/staker")
+                .and_then(|staker| staker.as_str())
+                .with_context(|| format!("Stake account {} has no staker", stake_account.pubkey))?;
+            owned += stake_account.account.lamports;
+            if staker == bonds_authority {
+                bond_data.number_active_stake_accounts += 1;
+            } else {
+                at_settlements += stake_account.account.lamports;
+                bond_data.number_settlement_stake_accounts += 1;
+            }
+        }
+        bond_data.funding_stake_accounts = Some(
+            stake_accounts
+                .into_iter()
+                .map(funding_stake_account)
+                .collect(),
+        );
+
+        let mut to_withdraw = 0;
+        if let Some(account) = withdraw_request {
+            let data = account_data(&account)?;
+            let (epoch, requested, withdrawn) =
+                decode_withdraw_request(&data).with_context(|| {
+                    format!("Invalid withdraw request {}", withdraw_request_address)
+                })?;
+            to_withdraw = requested.saturating_sub(withdrawn);
+            bond_data.withdraw_request = WithdrawRequest::Pending {
+                amount: requested as f64 / LAMPORTS_PER_SOL as f64,
+                epoch,
+            };
+        }
+
+        bond_data.amount_owned = format_sol(owned);
+        bond_data.amount_at_settlements = format_sol(at_settlements);
+        bond_data.amount_to_withdraw = format_sol(to_withdraw);
+        bond_data.amount_active = format_sol(
+            owned
+                .saturating_sub(at_settlements)
+                .saturating_sub(to_withdraw),
+        );
+        Ok(())
+    }
+}
+
+/// Stake accounts funded to the bond of the address, read like
+/// [RpcBackend::get_bond] for bonds which the CLI showed
+pub async fn get_funding_stake_accounts(
+    client: &RpcClient,
+    addr: &Address,
+    bond_data: &BondData,
+    timeout: std::time::Duration,
+) -> Result<Vec<FundingStakeAccount>, FetchError> {
+    read_with_timeout(client, addr, timeout, |client| async move {
+        let bonds_authority = bonds_authority(bond_data)?;
+        let stake_accounts = client
+            .get_program_accounts(
+                STAKE_PROGRAM_ID,
+                "jsonParsed",
+                funding_stake_filters(&bonds_authority, bond_data)?,
+            )
+            .await?;
+        Ok(stake_accounts
+            .into_iter()
+            .map(funding_stake_account)
+            .collect())
+    })
+    .await
+}
+
+/// Runs `read` with the `cli_rpc_url` of the address or the endpoint of the
+/// client, failing after `timeout`. Errors which are no [FetchError] become
+/// [FetchError::Rpc].
+async fn read_with_timeout<T, F>(
+    client: &RpcClient,
+    addr: &Address,
+    timeout: std::time::Duration,
+    read: impl FnOnce(RpcClient) -> F,
+) -> Result<T, FetchError>
+where
+    F: std::future::Future<Output = anyhow::Result<T>>,
+{
+    let client = match &addr.cli_rpc_url {
+        Some(url) => client.with_url(url.clone()),
+        None => client.clone(),
+    };
+    let url = redact_url(client.url());
+    match tokio::time::timeout(timeout, read(client)).await {
+        Ok(res) => res.map_err(|err| match err.downcast::<FetchError>() {
+            Ok(err) => err,
+            Err(err) => FetchError::Rpc { url, err },
+        }),
+        Err(_) => Err(FetchError::Rpc {
+            url,
+            err: anyhow::anyhow!("No response within {}", humantime::format_duration(timeout)),
+        }),
+    }
+}
+
+/// Withdrawer of all stake accounts funded to bonds of the config of the bond
+fn bonds_authority(bond_data: &BondData) -> anyhow::Result<String> {
+    Ok(bs58::encode(find_program_address(
+        &[
+            b"bonds_authority",
+            &decode_pubkey(&bond_data.account.config)?,
+        ],
+        &decode_pubkey(&bond_data.program_id)?,
+    ))
+    .into_string())
+}
+
+/// Stake accounts of the bonds authority delegated to the vote account of
+/// the bond, including those handed over to settlements
+fn funding_stake_filters(
+    bonds_authority: &str,
+    bond_data: &BondData,
+) -> anyhow::Result<Vec<serde_json::Value>> {
+    Ok(vec![
+        memcmp(STAKE_WITHDRAWER_OFFSET, &decode_pubkey(bonds_authority)?),
+        memcmp(
+            STAKE_VOTER_OFFSET,
+            &decode_pubkey(&bond_data.account.vote_account)?,
+        ),
+    ])
+}
+
+/// Lamports and delegation epochs of a `jsonParsed` stake account
+fn funding_stake_account(stake_account: rpc::KeyedAccount) -> FundingStakeAccount {
+    let delegation_epoch = |name: &str| {
+        stake_account
+            .account
+            .data
+            .pointer(&format!("/parsed/info/stake/delegation/{}", name))
+            .and_then(|epoch| epoch.as_str())
+            .and_then(|epoch| epoch.parse::<u64>().ok())
+    };
+    FundingStakeAccount {
+        activation_epoch: delegation_epoch("activationEpoch"),
+        deactivation_epoch: delegation_epoch("deactivationEpoch"),
+        lamports: stake_account.account.lamports,
+        public_key: stake_account.pubkey,
+    }
+}
+
+/// Bytes of an account requested with `base64` encoding
+fn account_data(account: &rpc::Account) -> anyhow::Result<Vec<u8>> {
+    use base64::Engine;
+
+    match account.data.as_array().map(Vec::as_slice) {
+        Some([data, encoding]) if encoding == "base64" => {
+            let data = data.as_str().context("Account data is not a string")?;
+            base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .context("Failed to decode account data")
+        }
+        _ => anyhow::bail!("Account data is not base64 encoded"),
+    }
+}
+
+fn decode_bond(data: &[u8]) -> anyhow::Result<Account> {
+    if data.len() < BOND_LEN {
+        anyhow::bail!("Bond account has {} bytes only", data.len());
+    }
+    let pubkey_at = |offset: usize| bs58::encode(&data[offset..offset + 32]).into_string();
+    Ok(Account {
+        config: pubkey_at(8),
+        vote_account: pubkey_at(BOND_VOTE_ACCOUNT_OFFSET),
+        authority: pubkey_at(72),
+    })
+}
+
+/// Epoch, requested and withdrawn lamports
+fn decode_withdraw_request(data: &[u8]) -> anyhow::Result<(u64, u64, u64)> {
+    if !data.starts_with(&WITHDRAW_REQUEST_DISCRIMINATOR) {
+        anyhow::bail!("Account is not a withdraw request");
+    }
+    if data.len() < WITHDRAW_REQUEST_LEN {
+        anyhow::bail!("Withdraw request has {} bytes only", data.len());
+    }
+    let u64_at = |offset: usize| {
+        u64::from_le_bytes(
+            data[offset..offset + 8]
+                .try_into()
+                .expect("slice has 8 bytes"),
+        )
+    };
+    Ok((
+        u64_at(WITHDRAW_REQUEST_EPOCH_OFFSET),
+        u64_at(WITHDRAW_REQUEST_EPOCH_OFFSET + 8),
+        u64_at(WITHDRAW_REQUEST_EPOCH_OFFSET + 16),
+    ))
+}
+
+fn decode_pubkey(pubkey: &str) -> anyhow::Result<[u8; 32]> {
+    bs58::decode(pubkey)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .with_context(|| format!("'{}' is not a pubkey", pubkey))
+}
+
+fn memcmp(offset: usize, bytes: &[u8]) -> serde_json::Value {
+    serde_json::json!({"memcmp": {"offset": offset, "bytes": bs58::encode(bytes).into_string()}})
+}
+
+/// Address derived from the seeds like Solana's `find_program_address`,
+/// with the highest bump which takes it off the ed25519 curve
+fn find_program_address(seeds: &[&[u8]], program_id: &[u8; 32]) -> [u8; 32] {
+    use sha2::Digest;
+
+    (0..=u8::MAX)
+        .rev()
+        .map(|bump| {
+            let mut hasher = sha2::Sha256::new();
+            for seed in seeds {
+                hasher.update(seed);
+            }
+            hasher.update([bump]);
+            hasher.update(program_id);
+            hasher.update(b"ProgramDerivedAddress");
+            <[u8; 32]>::from(hasher.finalize())
+        })
+        .find(|hash| {
+            curve25519_dalek::edwards::CompressedEdwardsY(*hash)
+                .decompress()
+                .is_none()
+        })
+        .expect("some bump should give an address off the curve")
+}
+
+/// Lamports as the CLI prints SOL amounts, like "1200.5 SOLs"
+fn format_sol(lamports: u64) -> String {
+    let fraction = format!(
+        "{:0width$}",
+        lamports % LAMPORTS_PER_SOL,
+        width = SOL_DECIMALS
+    );
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{} SOLs", lamports / LAMPORTS_PER_SOL)
+    } else {
+        format!("{}.{} SOLs", lamports / LAMPORTS_PER_SOL, fraction)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::tests::test_address;
+    use crate::StakeState;
+
+    const BOND_ACCOUNT: &str = include_str!("../tests/fixtures/rpc/bond_account.json");
+    const BONDS_OF_VOTE_ACCOUNT: &str =
+        include_str!("../tests/fixtures/rpc/bonds_of_vote_account.json");
+    const VOTE_ACCOUNT: &str = include_str!("../tests/fixtures/rpc/vote_account.json");
+    const STAKE_ACCOUNTS: &str = include_str!("../tests/fixtures/rpc/stake_accounts.json");
+    const WITHDRAW_REQUEST: &str = include_str!("../tests/fixtures/rpc/withdraw_request.json");
+
+    const VOTE_ACCOUNT_ADDRESS: &str = "BbwQeqSparWokppbNK4GtvnQQsrth9Wzjxm48VDXCaJb";
+    /// Derived from the bond of the fixtures
+    const WITHDRAW_REQUEST_ADDRESS: &str = "FAmb983BDxASQttFJ4JpfYFh3a7RMWEuueA9LXheQcvU";
+
+    /// Serves recorded JSON-RPC responses by method and first parameter, or
+    /// an empty one if that is no string, and returns its URL. Other accounts
+    /// do not exist.
+    async fn mock_accounts(responses: Vec<(&'static str, &'static str, &'static str)>) -> String {
+        let responses: std::collections::HashMap<_, serde_json::Value> = responses
+            .into_iter()
+            .map(|(method, param, response)| {
+                ((method, param), serde_json::from_str(response).unwrap())
+            })
+            .collect();
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(move |axum::Json(request): axum::Json<serde_json::Value>| {
+                let method = request["method"].as_str().unwrap().to_string();
+                let param = request["params"][0]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                let result = responses
+                    .get(&(method.as_str(), param.as_str()))
+                    .cloned()
+                    .unwrap_or_else(|| match method.as_str() {
+                        "getProgramAccounts" => serde_json::json!([]),
+                        _ => serde_json::json!({"context": {"slot": 1}, "value": null}),
+                    });
+                async move {
+                    axum::Json(serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": result}))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    fn funding_responses() -> Vec<(&'static str, &'static str, &'static str)> {
+        vec![
+            ("getAccountInfo", VOTE_ACCOUNT_ADDRESS, VOTE_ACCOUNT),
+            ("getProgramAccounts", STAKE_PROGRAM_ID, STAKE_ACCOUNTS),
+        ]
+    }
+
+    /// Serves the bond of [test_address] with a withdraw request and
+    /// returns its URL
+    pub(crate) async fn mock_bond_rpc() -> String {
+        let mut responses = funding_responses();
+        responses.extend([
+            (
+                "getAccountInfo",
+                "BdLK3AHdzfSHKDpdT7xCHNt4XQFBeFekrtFyTqD3evrQ",
+                BOND_ACCOUNT,
+            ),
+            ("getAccountInfo", WITHDRAW_REQUEST_ADDRESS, WITHDRAW_REQUEST),
+        ]);
+        mock_accounts(responses).await
+    }
+
+    #[tokio::test]
+    async fn reads_bond_account() {
+        let client = RpcClient::new(mock_bond_rpc().await);
+
+        let bond_data = RpcBackend::new(None)
+            .get_bond(&client, &test_address(), std::time::Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(bond_data.program_id, BONDS_PROGRAM_ID);
+        assert_eq!(
+            bond_data.public_key,
+            "BdLK3AHdzfSHKDpdT7xCHNt4XQFBeFekrtFyTqD3evrQ"
+        );
+        assert_eq!(
+            bond_data.account.config,
+            "GECDRGVTvfa5vfrCEPxibfDnJMmTDPX4CJPoWmfDs6rr"
+        );
+        assert_eq!(bond_data.account.vote_account, VOTE_ACCOUNT_ADDRESS);
+        assert_eq!(
+            bond_data.account.authority,
+            "CMyoB8Yc7NBHhZwn9oz2wLBJW5EoZeuj9kWdFXKooDhJ"
+        );
+        let vote_account = bond_data.vote_account.as_ref().unwrap();
+        assert_eq!(
+            vote_account.node_pubkey,
+            "G795LTaKPbC7eyRePBTey9VZAwEygJGqinnLRuXX2E4T"
+        );
+        assert_eq!(
+            vote_account.authorized_withdrawer,
+            "JCZfnzJi9oxmURtdMMoyrEQYN6foCr6LHoxQRj5FRjGj"
+        );
+        assert_eq!(vote_account.commission, 5);
+        assert_eq!(
+            bond_data.bond_mint.as_deref(),
+            Some("9dk1k2w61GaZENaU3FdqwWyFRZy7PU6U8q7pCSAHebs7")
+        );
+        assert_eq!(bond_data.amount_owned, "1750.75 SOLs");
+        assert_eq!(bond_data.amount_at_settlements, "250.25 SOLs");
+        assert_eq!(bond_data.amount_to_withdraw, "300.5 SOLs");
+        assert_eq!(bond_data.amount_active, "1200 SOLs");
+        assert_eq!(bond_data.number_active_stake_accounts, 3);
+        assert_eq!(bond_data.number_settlement_stake_accounts, 1);
+        assert_eq!(
+            bond_data.withdraw_request,
+            WithdrawRequest::Pending {
+                amount: 300.5,
+                epoch: 600
+            }
+        );
+        assert_eq!(bond_data.active_amount_sol().unwrap(), 1200.0);
+    }
+
+    #[tokio::test]
+    async fn finds_bond_of_vote_account() {
+        let mut responses = funding_responses();
+        responses.push((
+            "getProgramAccounts",
+            BONDS_PROGRAM_ID,
+            BONDS_OF_VOTE_ACCOUNT,
+        ));
+        let client = RpcClient::new(mock_accounts(responses).await);
+        let addr = Address {
+            address: VOTE_ACCOUNT_ADDRESS.to_string(),
+            ..test_address()
+        };
+
+        let bond_data = RpcBackend::new(None)
+            .get_bond(&client, &addr, std::time::Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            bond_data.public_key,
+            "BdLK3AHdzfSHKDpdT7xCHNt4XQFBeFekrtFyTqD3evrQ"
+        );
+        assert_eq!(bond_data.withdraw_request, WithdrawRequest::None);
+        assert_eq!(bond_data.amount_to_withdraw, "0 SOLs");
+        assert_eq!(bond_data.amount_active, "1500.5 SOLs");
+    }
+
+    #[tokio::test]
+    async fn reports_missing_bond() {
+        let client = RpcClient::new(mock_accounts(funding_responses()).await);
+
+        let err = RpcBackend::new(None)
+            .get_bond(&client, &test_address(), std::time::Duration::from_secs(5))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, FetchError::BondNotFound { .. }), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn lists_funding_stake_accounts() {
+        let client = RpcClient::new(mock_bond_rpc().await);
+        let mut bond_data = RpcBackend::new(None)
+            .get_bond(&client, &test_address(), std::time::Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        let listed = bond_data.funding_stake_accounts.take().unwrap();
+        let stake_accounts = get_funding_stake_accounts(
+            &client,
+            &test_address(),
+            &bond_data,
+            std::time::Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+
+        let in_epoch_720 = |stake_accounts: &[FundingStakeAccount]| {
+            stake_accounts
+                .iter()
+                .map(|stake_account| {
+                    (
+                        stake_account.public_key.clone(),
+                        stake_account.lamports,
+                        stake_account.state(720),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(in_epoch_720(&listed), in_epoch_720(&stake_accounts));
+        assert_eq!(
+            in_epoch_720(&stake_accounts),
+            [
+                (
+                    "D65x5bvKVPCw31QW9JuMXYHEPrHEQaB1h2hFvE6sBsk4".to_string(),
+                    700_000_000_000,
+                    StakeState::Active
+                ),
+                (
+                    "9MkFSKyp3AhZexsbvKvDv8QCoAbP3UqV8cSiZXqbPD8U".to_string(),
+                    500_500_000_000,
+                    StakeState::Activating
+                ),
+                (
+                    "GqT45smpGpnQMnCvySKWKUzzreZhqHdiTezD8vH3RR3y".to_string(),
+                    250_250_000_000,
+                    StakeState::Active
+                ),
+                (
+                    "2s2xNnTWwuRPF9dH1zLqg57HjUripKhHK4DZAQVVXNXa".to_string(),
+                    300_000_000_000,
+                    StakeState::Deactivating
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn derives_stake_state() {
+        let stake_account = |data| {
+            funding_stake_account(rpc::KeyedAccount {
+                pubkey: "D65x5bvKVPCw31QW9JuMXYHEPrHEQaB1h2hFvE6sBsk4".to_string(),
+                account: rpc::Account {
+                    lamports: 1,
+                    owner: STAKE_PROGRAM_ID.to_string(),
+                    data,
+                },
+            })
+        };
+        let delegated = |activation: &str, deactivation: &str| {
+            stake_account(
+                serde_json::json!({"parsed": {"type": "delegated", "info": {"stake": {"delegation": {
+                    "activationEpoch": activation,
+                    "deactivationEpoch": deactivation
+                }}}}}),
+            )
+        };
+        let never = u64::MAX.to_string();
+
+        assert_eq!(delegated("719", &never).state(720), StakeState::Active);
+        assert_eq!(delegated("720", &never).state(720), StakeState::Activating);
+        assert_eq!(delegated("700", "720").state(720), StakeState::Deactivating);
+        assert_eq!(delegated("700", "719").state(720), StakeState::Inactive);
+        assert_eq!(delegated("720", "720").state(720), StakeState::Inactive);
+        assert_eq!(
+            stake_account(serde_json::json!({"parsed": {"type": "initialized", "info": {}}}))
+                .state(720),
+            StakeState::Inactive
+        );
+    }
+
+    #[test]
+    fn formats_sol_amounts_like_the_cli() {
+        assert_eq!(format_sol(0), "0 SOLs");
+        assert_eq!(format_sol(1), "0.000000001 SOLs");
+        assert_eq!(format_sol(1_200_500_000_000), "1200.5 SOLs");
+        assert_eq!(
+            crate::parse_sol_amount_lamports(&format_sol(123_456_789_012)).unwrap(),
+            123_456_789_012
+        );
+    }
+}